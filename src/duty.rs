@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use log::{error, warn};
+use tokio::sync::{mpsc, oneshot, RwLock};
+
+/// Current lifecycle state of a [`Duty`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DutyState {
+    Idle,
+    Running,
+    Failed,
+}
+
+/// Outcome of a single [`Duty::run`] invocation.
+#[derive(Debug, Clone, Default)]
+pub struct DutyReport {
+    pub containers_checked: usize,
+    pub containers_updated: usize,
+    pub containers_skipped: usize,
+    /// Images or build cache entries removed, for the prune duties.
+    pub items_removed: usize,
+    pub bytes_reclaimed: u64,
+    pub errors: Vec<String>,
+}
+
+/// A housekeeping chore that can be run, queried and paused independently of the others.
+#[async_trait]
+pub trait Duty: Send + Sync {
+    /// Short, stable name used to address this duty over the control channel (e.g. `"update_images"`).
+    fn name(&self) -> &'static str;
+
+    /// The duty's state as of its last `run` call.
+    fn state(&self) -> DutyState;
+
+    /// Runs the duty once, updating its internal state before returning the report.
+    async fn run(&mut self) -> DutyReport;
+}
+
+/// A command sent to a running [`DutyManager`] over its control channel.
+pub enum DutyCommand {
+    /// Trigger a one-off round of every non-paused duty and report back once it's done.
+    RunOnce { respond_to: oneshot::Sender<Vec<(String, DutyReport)>> },
+    /// Pause a duty by name; future rounds will skip it until it's resumed.
+    Pause { duty: String },
+    /// Resume a previously paused duty.
+    Resume { duty: String },
+    /// Ask for the current state and last report of every registered duty.
+    Status { respond_to: oneshot::Sender<Vec<DutyStatus>> },
+}
+
+/// Snapshot of a single duty's health, returned by [`DutyCommand::Status`].
+#[derive(Debug, Clone)]
+pub struct DutyStatus {
+    pub name: String,
+    pub state: DutyState,
+    pub paused: bool,
+    pub last_report: Option<DutyReport>,
+}
+
+/// One running [`DutyManager`]'s handle per endpoint, kept alive for the life of the process.
+pub type DutyManagers = std::sync::Arc<std::collections::HashMap<String, DutyManagerHandle>>;
+
+/// Handle used by operators to talk to a [`DutyManager`] running in the background.
+#[derive(Clone)]
+pub struct DutyManagerHandle {
+    commands: mpsc::Sender<DutyCommand>,
+}
+
+impl DutyManagerHandle {
+    /// Triggers an immediate, one-off housekeeping round and waits for its reports.
+    pub async fn run_once(&self) -> Vec<(String, DutyReport)> {
+        let (respond_to, response) = oneshot::channel();
+        if self.commands.send(DutyCommand::RunOnce { respond_to }).await.is_err() {
+            error!("Duty manager is not running, one-off round was dropped.");
+            return Vec::new();
+        }
+        response.await.unwrap_or_default()
+    }
+
+    /// Pauses a duty by name; it will be skipped by every round until resumed.
+    pub async fn pause(&self, duty: impl Into<String>) {
+        let _ = self.commands.send(DutyCommand::Pause { duty: duty.into() }).await;
+    }
+
+    /// Resumes a previously paused duty.
+    pub async fn resume(&self, duty: impl Into<String>) {
+        let _ = self.commands.send(DutyCommand::Resume { duty: duty.into() }).await;
+    }
+
+    /// Returns the current state, pause flag and last report of every registered duty.
+    pub async fn status(&self) -> Vec<DutyStatus> {
+        let (respond_to, response) = oneshot::channel();
+        if self.commands.send(DutyCommand::Status { respond_to }).await.is_err() {
+            error!("Duty manager is not running, status query was dropped.");
+            return Vec::new();
+        }
+        response.await.unwrap_or_default()
+    }
+}
+
+/// Owns the registered duties and serves commands sent over its control channel.
+pub struct DutyManager {
+    duties: std::sync::Arc<RwLock<Vec<Box<dyn Duty>>>>,
+    paused: std::sync::Arc<RwLock<std::collections::HashSet<String>>>,
+    last_reports: std::sync::Arc<RwLock<std::collections::HashMap<String, DutyReport>>>,
+    commands: mpsc::Receiver<DutyCommand>,
+}
+
+impl DutyManager {
+    /// Registers the given duties and spawns the manager's command loop in the background,
+    /// returning a [`DutyManagerHandle`] operators can use to control it.
+    pub fn spawn(duties: Vec<Box<dyn Duty>>) -> DutyManagerHandle {
+        let (commands_tx, commands_rx) = mpsc::channel(16);
+
+        let manager = Self {
+            duties: std::sync::Arc::new(RwLock::new(duties)),
+            paused: std::sync::Arc::new(RwLock::new(std::collections::HashSet::new())),
+            last_reports: std::sync::Arc::new(RwLock::new(std::collections::HashMap::new())),
+            commands: commands_rx,
+        };
+
+        tokio::spawn(manager.serve());
+
+        DutyManagerHandle { commands: commands_tx }
+    }
+
+    async fn serve(mut self) {
+        while let Some(command) = self.commands.recv().await {
+            match command {
+                DutyCommand::RunOnce { respond_to } => {
+                    let reports = self.run_round().await;
+                    let _ = respond_to.send(reports);
+                }
+                DutyCommand::Pause { duty } => {
+                    warn!("Pausing duty `{}`.", duty);
+                    self.paused.write().await.insert(duty);
+                }
+                DutyCommand::Resume { duty } => {
+                    self.paused.write().await.remove(&duty);
+                }
+                DutyCommand::Status { respond_to } => {
+                    let statuses = self.statuses().await;
+                    let _ = respond_to.send(statuses);
+                }
+            }
+        }
+    }
+
+    async fn statuses(&self) -> Vec<DutyStatus> {
+        let duties = self.duties.read().await;
+        let paused = self.paused.read().await;
+        let last_reports = self.last_reports.read().await;
+
+        duties
+            .iter()
+            .map(|duty| DutyStatus {
+                name: duty.name().to_string(),
+                state: duty.state(),
+                paused: paused.contains(duty.name()),
+                last_report: last_reports.get(duty.name()).cloned(),
+            })
+            .collect()
+    }
+
+    /// Runs every non-paused duty once and records its report.
+    async fn run_round(&self) -> Vec<(String, DutyReport)> {
+        let paused = self.paused.read().await.clone();
+        let mut duties = self.duties.write().await;
+        let mut reports = Vec::with_capacity(duties.len());
+
+        for duty in duties.iter_mut() {
+            if paused.contains(duty.name()) {
+                warn!("Duty `{}` is paused, skipping.", duty.name());
+                continue;
+            }
+
+            let report = duty.run().await;
+            if !report.errors.is_empty() {
+                error!("Duty `{}` reported `{}` error(s).", duty.name(), report.errors.len());
+            }
+
+            self.last_reports.write().await.insert(duty.name().to_string(), report.clone());
+            reports.push((duty.name().to_string(), report));
+        }
+
+        reports
+    }
+}
@@ -0,0 +1,101 @@
+use crate::utils::connect_to_docker;
+use bollard::Docker;
+use log::{error, info};
+use std::env;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Default timeout applied to the Docker API client when connecting to a remote endpoint.
+const DEFAULT_API_TIMEOUT_SECS: u64 = 120;
+
+/// A named Docker endpoint the maid tends, alongside the connection it resolved to.
+pub struct DockerEndpoint {
+    pub name: String,
+    pub docker: Docker,
+}
+
+/// Registry of every Docker endpoint the maid is configured to tend.
+pub type EndpointRegistry = Arc<RwLock<Vec<DockerEndpoint>>>;
+
+/// Reads `MAID_ENDPOINTS` and connects to every endpoint it describes.
+///
+/// Format: a `;`-separated list of `name=connection` pairs, where `connection` is one of:
+/// - `socket`: the local `/var/run/docker.sock`
+/// - `tcp://host:port`: a plain remote host
+/// - `tls://host:port|ca_path|cert_path|key_path`: a TLS-secured remote host with a client certificate
+///
+/// Example:
+/// `MAID_ENDPOINTS=local=socket;edge=tcp://edge.internal:2375;prod=tls://prod.internal:2376|/certs/ca.pem|/certs/cert.pem|/certs/key.pem`
+///
+/// Falls back to a single `"local"` endpoint over the local socket when unset. If it's set but
+/// every entry fails to parse or connect, returns the collected errors instead of silently
+/// falling back, since that fallback would otherwise panic on the local socket anyway.
+pub fn connect_to_endpoints() -> Result<EndpointRegistry, String> {
+    let endpoints_string = match env::var("MAID_ENDPOINTS") {
+        Ok(value) if !value.trim().is_empty() => value,
+        _ => {
+            info!("MAID_ENDPOINTS not set, tending only the local docker socket.");
+            return Ok(Arc::new(RwLock::new(vec![DockerEndpoint {
+                name: "local".to_string(),
+                docker: connect_to_docker(),
+            }])));
+        }
+    };
+
+    let mut endpoints = Vec::new();
+    let mut failures = Vec::new();
+    for entry in endpoints_string.split(';').map(str::trim).filter(|entry| !entry.is_empty()) {
+        match parse_endpoint(entry) {
+            Ok(endpoint) => endpoints.push(endpoint),
+            Err(e) => {
+                error!("\t-> Skipping invalid endpoint `{}`. ({})", entry, e);
+                failures.push(format!("`{}`: {}", entry, e));
+            }
+        }
+    }
+
+    if endpoints.is_empty() {
+        return Err(format!(
+            "MAID_ENDPOINTS was set but none of its {} endpoint(s) could be reached: {}",
+            failures.len(),
+            failures.join("; ")
+        ));
+    }
+
+    Ok(Arc::new(RwLock::new(endpoints)))
+}
+
+/// Parses a single `name=connection` entry into a connected [`DockerEndpoint`].
+fn parse_endpoint(entry: &str) -> Result<DockerEndpoint, String> {
+    let (name, connection) = entry.split_once('=').ok_or_else(|| "expected `name=connection`".to_string())?;
+
+    let timeout = DEFAULT_API_TIMEOUT_SECS;
+
+    let docker = if connection == "socket" {
+        connect_to_docker()
+    } else if let Some(address) = connection.strip_prefix("tcp://") {
+        Docker::connect_with_http(address, timeout, bollard::API_DEFAULT_VERSION)
+            .map_err(|e| format!("failed to connect over TCP: {}", e))?
+    } else if let Some(rest) = connection.strip_prefix("tls://") {
+        let mut parts = rest.splitn(4, '|');
+        let address = parts.next().filter(|s| !s.is_empty()).ok_or_else(|| "missing host:port".to_string())?;
+        let ca_path = parts.next().filter(|s| !s.is_empty()).ok_or_else(|| "missing CA path".to_string())?;
+        let cert_path = parts.next().filter(|s| !s.is_empty()).ok_or_else(|| "missing client cert path".to_string())?;
+        let key_path = parts.next().filter(|s| !s.is_empty()).ok_or_else(|| "missing client key path".to_string())?;
+
+        Docker::connect_with_ssl(
+            address,
+            std::path::Path::new(key_path),
+            std::path::Path::new(cert_path),
+            std::path::Path::new(ca_path),
+            timeout,
+            bollard::API_DEFAULT_VERSION,
+        )
+        .map_err(|e| format!("failed to connect over TLS: {}", e))?
+    } else {
+        return Err(format!("unrecognized connection scheme `{}`, expected `socket`, `tcp://` or `tls://`", connection));
+    };
+
+    info!("\t-> Connected to endpoint `{}`.", name);
+    Ok(DockerEndpoint { name: name.to_string(), docker })
+}
@@ -1,60 +1,206 @@
-use crate::enums::{ImagesPruneMode, ContainersUpdateMode};
-use crate::utils::{connect_to_docker, get_all_containers, pull_image};
+use crate::duty::{Duty, DutyManager, DutyManagers, DutyReport, DutyState};
+use crate::endpoints::EndpointRegistry;
+use crate::enums::{BuildPruneMode, ImagesPruneMode, ContainersUpdateMode};
+use crate::metrics::metrics;
+use crate::state::{now_seconds, StateHandle};
+use crate::utils::{get_all_containers, pull_image};
+use async_trait::async_trait;
 use bollard::Docker;
-use bollard::models::{ContainerCreateBody, ContainerSummaryStateEnum};
+use bollard::models::{ContainerCreateBody, ContainerStateStatusEnum, ContainerSummaryStateEnum, HealthStatusEnum};
 use bollard::query_parameters::{
-    CreateContainerOptionsBuilder, InspectContainerOptionsBuilder, PruneImagesOptionsBuilder,
-    RemoveContainerOptionsBuilder, StartContainerOptionsBuilder, StopContainerOptionsBuilder,
+    CreateContainerOptionsBuilder, InspectContainerOptionsBuilder, PruneBuildOptionsBuilder,
+    PruneImagesOptionsBuilder, RemoveContainerOptionsBuilder, StartContainerOptionsBuilder,
+    StopContainerOptionsBuilder,
 };
 use log::{error, info, warn};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Registers a duty manager for every configured endpoint, one duty per enabled chore. The
+/// managers run for the life of the process so their control channel stays reachable between
+/// rounds instead of being spun up and torn down every time.
+pub async fn spawn_duty_managers(endpoints: &EndpointRegistry, state: StateHandle) -> DutyManagers {
+    let update_mode = ContainersUpdateMode::from_env();
+    let prune_mode = ImagesPruneMode::from_env();
+    let build_prune_mode = BuildPruneMode::from_env();
+
+    let endpoints = endpoints.read().await;
+    let mut managers = HashMap::new();
+
+    for endpoint in endpoints.iter() {
+        let mut duties: Vec<Box<dyn Duty>> = Vec::new();
+
+        if update_mode != ContainersUpdateMode::None {
+            duties.push(Box::new(UpdateImages {
+                docker: endpoint.docker.clone(),
+                update_mode,
+                endpoint_name: endpoint.name.clone(),
+                state_store: state.clone(),
+                state: DutyState::Idle,
+            }));
+        }
+
+        if prune_mode != ImagesPruneMode::None {
+            duties.push(Box::new(PruneImages {
+                docker: endpoint.docker.clone(),
+                prune_mode,
+                state: DutyState::Idle,
+            }));
+        }
+
+        if build_prune_mode != BuildPruneMode::None {
+            duties.push(Box::new(PruneBuildCache {
+                docker: endpoint.docker.clone(),
+                prune_mode: build_prune_mode,
+                state: DutyState::Idle,
+            }));
+        }
 
-pub async fn housekeeping() {
+        if duties.is_empty() {
+            info!("\t-> [{}] No duties are configured for this endpoint.", endpoint.name);
+            continue;
+        }
+
+        managers.insert(endpoint.name.clone(), DutyManager::spawn(duties));
+    }
+
+    Arc::new(managers)
+}
+
+/// Runs one housekeeping round by triggering every endpoint's duty manager.
+pub async fn housekeeping(duty_managers: &DutyManagers, state: &StateHandle) {
     info!("Housekeeping duties underway.");
 
-    // Read environment variables
-    let update_mode = ContainersUpdateMode::from_env();
-    let prune_mode = ImagesPruneMode::from_env();
+    let round_started_at = SystemTime::now();
+    let round_timer = Instant::now();
 
-    // Connect to docker socket
-    let docker = connect_to_docker();
+    for (endpoint_name, handle) in duty_managers.iter() {
+        info!("\t-> Tending endpoint `{}`...", endpoint_name);
 
-    // Update containers if UpdateMode is not None
-    if update_mode != ContainersUpdateMode::None {
-        update_images(&update_mode, &docker).await;
+        for (name, report) in handle.run_once().await {
+            record_report_metrics(&name, &report);
+
+            if report.errors.is_empty() {
+                info!("\t\t-> [{}] Duty `{}` finished successfully.", endpoint_name, name);
+            } else {
+                warn!(
+                    "\t\t-> [{}] Duty `{}` finished with `{}` error(s).",
+                    endpoint_name, name,
+                    report.errors.len()
+                );
+            }
+        }
     }
 
-    // Prune images if PruneMode is not None
-    if prune_mode != ImagesPruneMode::None {
-        prune_images(&prune_mode, &docker).await;
+    state.read().await.save();
+    metrics().record_round(round_started_at, round_timer.elapsed().as_secs());
+}
+
+/// Folds a single duty's report into the process-wide Prometheus metrics.
+fn record_report_metrics(duty_name: &str, report: &DutyReport) {
+    let metrics = metrics();
+
+    metrics.containers_checked_total.fetch_add(report.containers_checked as u64, Ordering::Relaxed);
+    metrics.containers_updated_total.fetch_add(report.containers_updated as u64, Ordering::Relaxed);
+    metrics.containers_skipped_total.fetch_add(report.containers_skipped as u64, Ordering::Relaxed);
+    metrics.bytes_reclaimed_total.fetch_add(report.bytes_reclaimed, Ordering::Relaxed);
+
+    match duty_name {
+        "prune_images" => {
+            metrics.images_pruned_total.fetch_add(report.items_removed as u64, Ordering::Relaxed);
+        }
+        "prune_build_cache" => {
+            metrics.build_cache_items_pruned_total.fetch_add(report.items_removed as u64, Ordering::Relaxed);
+        }
+        "update_images" => {
+            metrics.update_failures_total.fetch_add(report.errors.len() as u64, Ordering::Relaxed);
+        }
+        _ => (),
     }
 }
 
 /// Duty: Checks for new container image digests and updates containers or notifies the user based on the `update_mode` setting.
-async fn update_images(update_mode: &ContainersUpdateMode, docker: &Docker) {
+struct UpdateImages {
+    docker: Docker,
+    update_mode: ContainersUpdateMode,
+    endpoint_name: String,
+    state_store: StateHandle,
+    state: DutyState,
+}
+
+#[async_trait]
+impl Duty for UpdateImages {
+    fn name(&self) -> &'static str {
+        "update_images"
+    }
+
+    fn state(&self) -> DutyState {
+        self.state
+    }
+
+    async fn run(&mut self) -> DutyReport {
+        self.state = DutyState::Running;
+        let report = update_images(&self.update_mode, &self.docker, &self.endpoint_name, &self.state_store).await;
+        self.state = if report.errors.is_empty() { DutyState::Idle } else { DutyState::Failed };
+        report
+    }
+}
+
+/// Default time a failed digest sits out before it's eligible to be retried.
+const DEFAULT_FAILED_UPDATE_COOLDOWN_SECS: u64 = 3600;
+
+/// Reads `MAID_FAILED_UPDATE_COOLDOWN` (in seconds) from the environment, falling back to
+/// `DEFAULT_FAILED_UPDATE_COOLDOWN_SECS` if unset or invalid.
+fn failed_update_cooldown() -> Duration {
+    let secs = std::env::var("MAID_FAILED_UPDATE_COOLDOWN")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_FAILED_UPDATE_COOLDOWN_SECS);
+
+    Duration::from_secs(secs)
+}
+
+async fn update_images(
+    update_mode: &ContainersUpdateMode,
+    docker: &Docker,
+    endpoint_name: &str,
+    state_store: &StateHandle,
+) -> DutyReport {
     // Updating tags to their latest digests
     info!("[DUTY] Checking for new container image digests...");
 
+    let mut report = DutyReport::default();
+
     // Fetch containers list
     let containers = match get_all_containers(&docker).await {
         Ok(containers) => containers,
         Err(e) => {
-            error!(
-                "\t-> Failed to fetch containers list, will retry on the next housekeeping round. (Internal error: `{}`).",
+            let message = format!(
+                "Failed to fetch containers list, will retry on the next housekeeping round. (Internal error: `{}`).",
                 e
             );
-            return;
+            error!("\t-> {}", message);
+            report.errors.push(message);
+            return report;
         }
     };
 
     info!("\t-> Found `{}` containers.", containers.len());
     info!("\t-> Processing the containers in `{}` mode", update_mode);
+
+    let mut pending_updates: Vec<PendingUpdate> = Vec::new();
+
     for container in containers {
+        report.containers_checked += 1;
+
         // Init
         let current_container_id = match container.id.as_deref() {
             Some(id) => id,
             None => {
                 error!("\t-> Failed to fetch container ID. Update skipped.");
+                report.containers_skipped += 1;
                 continue;
             }
         };
@@ -63,6 +209,7 @@ async fn update_images(update_mode: &ContainersUpdateMode, docker: &Docker) {
             Some(id) => id.concat(),
             None => {
                 error!("\t-> Failed to fetch container name. Update skipped.");
+                report.containers_skipped += 1;
                 continue;
             }
         };
@@ -74,6 +221,7 @@ async fn update_images(update_mode: &ContainersUpdateMode, docker: &Docker) {
                     "\t-> Container `{}` has no state information. Update skipped.",
                     current_container_name
                 );
+                report.containers_skipped += 1;
                 continue;
             }
         };
@@ -86,6 +234,7 @@ async fn update_images(update_mode: &ContainersUpdateMode, docker: &Docker) {
                     "\t\t-> Container `{}` has no image information. Update skipped.",
                     container.names.unwrap_or_default().concat()
                 );
+                report.containers_skipped += 1;
                 continue;
             }
         };
@@ -99,6 +248,7 @@ async fn update_images(update_mode: &ContainersUpdateMode, docker: &Docker) {
                     "\t\t-> Container `{}` has no image ID. Update skipped.",
                     current_container_name
                 );
+                report.containers_skipped += 1;
                 continue;
             }
         };
@@ -110,6 +260,7 @@ async fn update_images(update_mode: &ContainersUpdateMode, docker: &Docker) {
                     "\t\t-> Failed to pull image `{}`. (Internal error: `{}`). Update skipped.",
                     image_name, e
                 );
+                report.containers_skipped += 1;
                 continue;
             }
         };
@@ -121,41 +272,235 @@ async fn update_images(update_mode: &ContainersUpdateMode, docker: &Docker) {
                     "\t\t-> Failed to inspect image `{}`. (Internal error: `{}`). Update skipped.",
                     image_name, e
                 );
+                report.containers_skipped += 1;
                 continue;
             }
         };
 
-        // If the image digest is unchanged, skip update
-        if &latest_digest == current_digest && false {
+        // A container is only genuinely out of date if the digest differs from the one it's
+        // currently running. A digest we already tried and failed on is only retried after it's
+        // been sitting in the cooldown for a while, so a permanently broken image doesn't get
+        // recreated every single round.
+        let state_key = format!("{}::{}::{}", endpoint_name, current_container_name, image_name);
+        let known_entry = state_store.read().await.entry(&state_key).cloned();
+
+        if &latest_digest == current_digest {
             info!("\t\t-> Container is up to date.");
+            state_store.write().await.record_check(&state_key, &latest_digest, false);
+            report.containers_skipped += 1;
             continue;
         }
 
+        if let Some(entry) = &known_entry {
+            if entry.digest == latest_digest {
+                if entry.failure_count == 0 {
+                    info!("\t\t-> Container is up to date.");
+                    report.containers_skipped += 1;
+                    continue;
+                }
+
+                let elapsed = now_seconds().saturating_sub(entry.last_check_seconds);
+                if elapsed < failed_update_cooldown().as_secs() {
+                    warn!(
+                        "\t\t-> Digest `{}` already failed `{}` time(s) for container `{}`, waiting out the cooldown before retrying.",
+                        latest_digest, entry.failure_count, current_container_name
+                    );
+                    report.containers_skipped += 1;
+                    continue;
+                }
+
+                info!(
+                    "\t\t-> Retrying digest `{}` for container `{}` after `{}` prior failure(s) and a cooldown.",
+                    latest_digest, current_container_name, entry.failure_count
+                );
+            }
+        }
+
         // Since we already pulled the latest digest to compare with the one used by the container,
         // we can safely update the container by restarting it.
         info!("\t\t-> New digest found for image `{}`", image_name);
 
         if update_mode == &ContainersUpdateMode::Update {
             info!(
-                "\t\t\t-> Container `{}` will be recreated.",
+                "\t\t\t-> Container `{}` is queued for recreation.",
                 current_container_name
             );
 
-            match update_container(&docker, current_container_id, &current_container_name, container_state).await {
-                Ok(_) => (),
-                Err(e) => {
-                    error!("\t\t\t-> {}. Update skipped.", e);
-                    continue;
-                }
-            };
+            let labels = container.labels.unwrap_or_default();
+            pending_updates.push(PendingUpdate {
+                container_id: current_container_id.to_string(),
+                container_name: current_container_name,
+                container_state: *container_state,
+                state_key,
+                latest_digest,
+                project: labels.get(COMPOSE_PROJECT_LABEL).cloned(),
+                service: labels.get(COMPOSE_SERVICE_LABEL).cloned(),
+                depends_on: labels
+                    .get(COMPOSE_DEPENDS_ON_LABEL)
+                    .map(|value| parse_depends_on(value))
+                    .unwrap_or_default(),
+            });
         } else {
             warn!("\t\t\t-> Container update not set to `Update`, skipping.");
+            report.containers_skipped += 1;
+        }
+    }
+
+    // Recreate updated containers in dependency order, so a compose service that depends on
+    // another one is only restarted once its dependency is back up.
+    for update in order_pending_updates(pending_updates) {
+        info!("\t\t\t-> Container `{}` will be recreated.", update.container_name);
+
+        match update_container(&docker, &update.container_id, &update.container_name, &update.container_state).await {
+            Ok(_) => {
+                report.containers_updated += 1;
+                state_store.write().await.record_check(&update.state_key, &update.latest_digest, true);
+            }
+            Err(e) => {
+                error!("\t\t\t-> {}. Update skipped.", e);
+                report.containers_skipped += 1;
+                state_store.write().await.record_failure(&update.state_key, &update.latest_digest);
+                report.errors.push(e);
+            }
+        };
+    }
+
+    report
+}
+
+/// Compose labels used to group containers by project/service and recreate them in dependency order.
+const COMPOSE_PROJECT_LABEL: &str = "com.docker.compose.project";
+const COMPOSE_SERVICE_LABEL: &str = "com.docker.compose.service";
+const COMPOSE_DEPENDS_ON_LABEL: &str = "com.docker.compose.depends_on";
+
+/// A container queued for recreation, along with the compose metadata needed to order it
+/// relative to the other containers in its project.
+struct PendingUpdate {
+    container_id: String,
+    container_name: String,
+    container_state: ContainerSummaryStateEnum,
+    /// Key this container/image pair is tracked under in the persisted digest store.
+    state_key: String,
+    /// Digest the container is being recreated to, recorded once the update succeeds.
+    latest_digest: String,
+    project: Option<String>,
+    service: Option<String>,
+    depends_on: Vec<String>,
+}
+
+/// Parses a compose `depends_on` label value (e.g. `db:service_started:true,cache:service_started:true`)
+/// into the list of service names it depends on.
+fn parse_depends_on(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .filter_map(|entry| entry.split(':').next())
+        .map(|service| service.trim().to_string())
+        .filter(|service| !service.is_empty())
+        .collect()
+}
+
+/// Orders pending updates so that, within each compose project, a service is recreated only
+/// after the services it depends on. Containers with no compose project label keep their
+/// original relative order among themselves, but are all recreated before any compose project's
+/// containers.
+fn order_pending_updates(pending: Vec<PendingUpdate>) -> Vec<PendingUpdate> {
+    let mut flat = Vec::new();
+    let mut projects: Vec<(String, Vec<PendingUpdate>)> = Vec::new();
+
+    for update in pending {
+        match &update.project {
+            None => flat.push(update),
+            Some(project) => match projects.iter_mut().find(|(name, _)| name == project) {
+                Some((_, group)) => group.push(update),
+                None => projects.push((project.clone(), vec![update])),
+            },
         }
     }
+
+    let mut ordered = flat;
+    for (project, group) in projects {
+        ordered.extend(topo_sort_project(&project, group));
+    }
+
+    ordered
 }
 
-/// Updates a container by stopping it, removing it and recreating it.
-/// Assumes that the latest digest of the image is locally available.
+/// Topologically sorts a single compose project's pending updates by `depends_on`, falling back
+/// to the original order if the dependency graph contains a cycle.
+fn topo_sort_project(project: &str, group: Vec<PendingUpdate>) -> Vec<PendingUpdate> {
+    let service_index: HashMap<&str, usize> = group
+        .iter()
+        .enumerate()
+        .filter_map(|(i, update)| update.service.as_deref().map(|service| (service, i)))
+        .collect();
+
+    let mut in_degree = vec![0usize; group.len()];
+    let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); group.len()];
+
+    for (i, update) in group.iter().enumerate() {
+        for dependency in &update.depends_on {
+            if let Some(&dependency_index) = service_index.get(dependency.as_str()) {
+                dependents[dependency_index].push(i);
+                in_degree[i] += 1;
+            }
+        }
+    }
+
+    let mut queue: VecDeque<usize> = in_degree
+        .iter()
+        .enumerate()
+        .filter(|(_, &degree)| degree == 0)
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut order = Vec::with_capacity(group.len());
+    while let Some(i) = queue.pop_front() {
+        order.push(i);
+        for &dependent in &dependents[i] {
+            in_degree[dependent] -= 1;
+            if in_degree[dependent] == 0 {
+                queue.push_back(dependent);
+            }
+        }
+    }
+
+    if order.len() != group.len() {
+        warn!(
+            "\t-> Compose project `{}` has a cyclic or unresolved `depends_on` graph, updating its services in their original order.",
+            project
+        );
+        return group;
+    }
+
+    let mut group: Vec<Option<PendingUpdate>> = group.into_iter().map(Some).collect();
+    order.into_iter().map(|i| group[i].take().unwrap()).collect()
+}
+
+/// Default time to wait for a newly recreated container to report healthy before rolling back.
+const DEFAULT_UPDATE_HEALTH_TIMEOUT_SECS: u64 = 60;
+
+/// Grace period given to a container with no healthcheck to stay up before we trust it.
+const NO_HEALTHCHECK_GRACE: Duration = Duration::from_secs(5);
+
+/// How often to poll `inspect_container` while waiting for a container to become healthy.
+const HEALTH_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Reads `MAID_UPDATE_HEALTH_TIMEOUT` (in seconds) from the environment, falling back to
+/// `DEFAULT_UPDATE_HEALTH_TIMEOUT_SECS` if unset or invalid.
+fn update_health_timeout() -> Duration {
+    let secs = std::env::var("MAID_UPDATE_HEALTH_TIMEOUT")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_UPDATE_HEALTH_TIMEOUT_SECS);
+
+    Duration::from_secs(secs)
+}
+
+/// Updates a container by stopping it, removing it and recreating it. If the container was
+/// running, the new one is started and must come up healthy before the update is trusted; if it
+/// fails that check, rolls back to the previously running image by recreating the container from
+/// the saved configuration. A container that was not running is recreated without being started
+/// or checked. Assumes that the latest digest of the image is locally available.
 async fn update_container(
     docker: &Docker,
     current_container_id: &str,
@@ -175,23 +520,18 @@ async fn update_container(
             )
         })?;
 
+    // Keep the previously running image around so we can pin a rollback to it, since the tag
+    // in the config below now points at the digest we just pulled.
+    let previous_image_id = container_inspect.image.clone();
+
+    let container_name = container_inspect.name.clone();
+    let container_platform = container_inspect.platform.clone();
+
     // Ensure we have a valid configuration to work with
     let new_config = container_inspect
         .config
         .ok_or_else(|| "Failed to fetch container configuration.".to_string())?;
 
-    // Prepare the container stop, remove and create options
-    let stop_container_options = StopContainerOptionsBuilder::new()
-        .t(10) // Wait 1 minutes before killing the container
-        .build();
-
-    let remove_container_options = RemoveContainerOptionsBuilder::new().build();
-
-    let create_container_options = CreateContainerOptionsBuilder::new()
-        .name(container_inspect.name.as_deref().unwrap_or_default())
-        .platform(container_inspect.platform.as_deref().unwrap_or_default())
-        .build();
-
     let container_create_body: ContainerCreateBody =
         serde_json::from_value(serde_json::to_value(new_config).map_err(|e| {
             format!(
@@ -206,72 +546,200 @@ async fn update_container(
             )
         })?;
 
+    let was_container_running = container_state == &ContainerSummaryStateEnum::RUNNING;
+
     // Perform the container operations
     info!("\t\t-> Stopping container...");
+    stop_and_remove(docker, current_container_id, current_container_name).await?;
+
+    info!("\t\t-> Recreating container...");
+    let new_container_id = recreate_and_start(
+        docker,
+        container_name.as_deref(),
+        container_platform.as_deref(),
+        container_create_body.clone(),
+        current_container_name,
+        was_container_running,
+    )
+    .await?;
+
+    if !was_container_running {
+        info!("\t\t-> Container update completed successfully.");
+        return Ok(());
+    }
+
+    info!("\t\t-> Waiting for container to report healthy...");
+    if let Err(e) = wait_until_healthy(docker, &new_container_id, current_container_name).await {
+        warn!(
+            "\t\t-> Container `{}` did not come up healthy, rolling back: {}",
+            current_container_name, e
+        );
+        metrics().rollbacks_total.fetch_add(1, Ordering::Relaxed);
+
+        let previous_image_id = previous_image_id
+            .ok_or_else(|| "Failed to roll back: previous image id is unknown.".to_string())?;
+
+        let mut rollback_body = container_create_body;
+        rollback_body.image = Some(previous_image_id);
+
+        stop_and_remove(docker, &new_container_id, current_container_name).await?;
+        recreate_and_start(
+            docker,
+            container_name.as_deref(),
+            container_platform.as_deref(),
+            rollback_body,
+            current_container_name,
+            was_container_running,
+        )
+        .await?;
+        metrics().rollbacks_total.fetch_add(1, Ordering::Relaxed);
+
+        return Err(format!(
+            "Container `{}` was rolled back to its previous image after failing its health check",
+            current_container_name
+        ));
+    }
+
+    info!("\t\t-> Container update completed successfully.");
+    Ok(())
+}
+
+/// Stops and removes a container, giving it `10` seconds to shut down gracefully.
+async fn stop_and_remove(docker: &Docker, container_id: &str, container_name: &str) -> Result<(), String> {
+    let stop_container_options = StopContainerOptionsBuilder::new()
+        .t(10) // Wait 10 seconds before killing the container
+        .build();
+
     docker
-        .stop_container(current_container_id, Some(stop_container_options))
+        .stop_container(container_id, Some(stop_container_options))
         .await
-        .map_err(|e| {
-            format!(
-                "Failed to stop container `{}`. (Internal error: `{}`).",
-                current_container_name, e
-            )
-        })?;
+        .map_err(|e| format!("Failed to stop container `{}`. (Internal error: `{}`).", container_name, e))?;
 
     info!("\t\t-> Removing container...");
+    let remove_container_options = RemoveContainerOptionsBuilder::new().build();
     docker
-        .remove_container(current_container_id, Some(remove_container_options))
+        .remove_container(container_id, Some(remove_container_options))
         .await
-        .map_err(|e| {
-            format!(
-                "Failed to remove container `{}`. (Internal error: `{}`).",
-                current_container_name, e
-            )
-        })?;
+        .map_err(|e| format!("Failed to remove container `{}`. (Internal error: `{}`).", container_name, e))
+}
+
+/// Creates a container from the given body and, if it was previously running, starts it.
+/// Returns the new container's id.
+async fn recreate_and_start(
+    docker: &Docker,
+    name: Option<&str>,
+    platform: Option<&str>,
+    body: ContainerCreateBody,
+    container_name: &str,
+    should_start: bool,
+) -> Result<String, String> {
+    let create_container_options = CreateContainerOptionsBuilder::new()
+        .name(name.unwrap_or_default())
+        .platform(platform.unwrap_or_default())
+        .build();
 
-    info!("\t\t-> Recreating container...");
     let create_container_response = docker
-        .create_container(Some(create_container_options), container_create_body)
+        .create_container(Some(create_container_options), body)
         .await
-        .map_err(|e| {
-            format!(
-                "Failed to create container `{}`. (Internal error: `{}`).",
-                current_container_name, e
-            )
-        })?;
+        .map_err(|e| format!("Failed to create container `{}`. (Internal error: `{}`).", container_name, e))?;
 
-    // Restart the container if it was running before
-    let was_container_running = container_state == &ContainerSummaryStateEnum::RUNNING;
-    info!(
-        "\t\t-> Should this container be restarted? `{}` (previous state was `{}`)",
-        if was_container_running { "yes" } else { "no" },
-        container_state
-    );
-    if container_state == &ContainerSummaryStateEnum::RUNNING {
+    if should_start {
         info!("\t\t-> Starting container...");
         let start_container_options = StartContainerOptionsBuilder::new().build();
         docker
-            .start_container(
-                create_container_response.id.to_owned().as_ref(),
-                Some(start_container_options),
-            )
+            .start_container(create_container_response.id.as_ref(), Some(start_container_options))
             .await
-            .map_err(|e| {
-                format!(
-                    "Failed to restart container `{}`. (Internal error: `{}`).",
-                    current_container_name, e
-                )
-            })?;
+            .map_err(|e| format!("Failed to start container `{}`. (Internal error: `{}`).", container_name, e))?;
     }
 
-    info!("\t\t-> Container update completed successfully.");
-    Ok(())
+    Ok(create_container_response.id)
+}
+
+/// Waits until the container reports a healthy status. Containers without a healthcheck are
+/// given a short grace period and then trusted as long as they're still running.
+async fn wait_until_healthy(docker: &Docker, container_id: &str, container_name: &str) -> Result<(), String> {
+    let deadline = tokio::time::Instant::now() + update_health_timeout();
+
+    loop {
+        let inspect_options = InspectContainerOptionsBuilder::new().build();
+        let inspect = docker
+            .inspect_container(container_id, Some(inspect_options))
+            .await
+            .map_err(|e| format!("Failed to inspect container `{}`. (Internal error: `{}`).", container_name, e))?;
+
+        let state = inspect.state.unwrap_or_default();
+
+        match state.health.and_then(|h| h.status) {
+            Some(HealthStatusEnum::HEALTHY) => return Ok(()),
+            Some(HealthStatusEnum::UNHEALTHY) => {
+                return Err(format!("Container `{}` reported an unhealthy status.", container_name));
+            }
+            Some(_) => {
+                // STARTING or another transitional status: keep polling until the deadline.
+            }
+            None => {
+                // No healthcheck configured: trust the container once it has survived the grace period.
+                tokio::time::sleep(NO_HEALTHCHECK_GRACE).await;
+
+                let inspect_options = InspectContainerOptionsBuilder::new().build();
+                let inspect = docker
+                    .inspect_container(container_id, Some(inspect_options))
+                    .await
+                    .map_err(|e| {
+                        format!("Failed to inspect container `{}`. (Internal error: `{}`).", container_name, e)
+                    })?;
+
+                return match inspect.state.and_then(|s| s.status) {
+                    Some(ContainerStateStatusEnum::RUNNING) => Ok(()),
+                    other => Err(format!(
+                        "Container `{}` is not running after the grace period (state: `{:?}`).",
+                        container_name, other
+                    )),
+                };
+            }
+        }
+
+        if tokio::time::Instant::now() >= deadline {
+            return Err(format!(
+                "Container `{}` did not become healthy within the configured timeout.",
+                container_name
+            ));
+        }
+
+        tokio::time::sleep(HEALTH_POLL_INTERVAL).await;
+    }
 }
 
 /// Duty: Prune `Dangling` or `All` unused images, depending on the `prune_mode` parameter.
-async fn prune_images(prune_mode: &ImagesPruneMode, docker: &Docker) {
+struct PruneImages {
+    docker: Docker,
+    prune_mode: ImagesPruneMode,
+    state: DutyState,
+}
+
+#[async_trait]
+impl Duty for PruneImages {
+    fn name(&self) -> &'static str {
+        "prune_images"
+    }
+
+    fn state(&self) -> DutyState {
+        self.state
+    }
+
+    async fn run(&mut self) -> DutyReport {
+        self.state = DutyState::Running;
+        let report = prune_images(&self.prune_mode, &self.docker).await;
+        self.state = if report.errors.is_empty() { DutyState::Idle } else { DutyState::Failed };
+        report
+    }
+}
+
+async fn prune_images(prune_mode: &ImagesPruneMode, docker: &Docker) -> DutyReport {
     info!("[DUTY] Pruning `{}` unused images...", prune_mode);
 
+    let mut report = DutyReport::default();
+
     let prune_images_options = PruneImagesOptionsBuilder::new()
         .filters(&HashMap::from([(
             "dangling",
@@ -282,17 +750,77 @@ async fn prune_images(prune_mode: &ImagesPruneMode, docker: &Docker) {
     let prune_images_response = match docker.prune_images(Some(prune_images_options)).await {
         Ok(response) => response,
         Err(e) => {
-            error!("\t-> Failed to prune images. (Internal error: `{}`).", e);
-            return;
+            let message = format!("Failed to prune images. (Internal error: `{}`).", e);
+            error!("\t-> {}", message);
+            report.errors.push(message);
+            return report;
         }
     };
 
+    let images_deleted = prune_images_response.images_deleted.unwrap_or_default().len();
+    let bytes_reclaimed = prune_images_response.space_reclaimed.unwrap_or_default().max(0) as u64;
+
     info!(
         "\t-> Prune completed successfully.\n\t\t- Removed `{}` images.\n\t\t- Reclaimed `{}` bytes.",
-        prune_images_response
-            .images_deleted
-            .unwrap_or_default()
-            .len(),
-        prune_images_response.space_reclaimed.unwrap_or_default()
+        images_deleted, bytes_reclaimed
     );
+
+    report.items_removed = images_deleted;
+    report.bytes_reclaimed = bytes_reclaimed;
+    report
+}
+
+/// Duty: Prune unused build cache, depending on the `prune_mode` parameter.
+struct PruneBuildCache {
+    docker: Docker,
+    prune_mode: BuildPruneMode,
+    state: DutyState,
+}
+
+#[async_trait]
+impl Duty for PruneBuildCache {
+    fn name(&self) -> &'static str {
+        "prune_build_cache"
+    }
+
+    fn state(&self) -> DutyState {
+        self.state
+    }
+
+    async fn run(&mut self) -> DutyReport {
+        self.state = DutyState::Running;
+        let report = prune_build_cache(&self.prune_mode, &self.docker).await;
+        self.state = if report.errors.is_empty() { DutyState::Idle } else { DutyState::Failed };
+        report
+    }
+}
+
+async fn prune_build_cache(prune_mode: &BuildPruneMode, docker: &Docker) -> DutyReport {
+    info!("[DUTY] Pruning `{}` unused build cache...", prune_mode);
+
+    let mut report = DutyReport::default();
+
+    let prune_build_options = PruneBuildOptionsBuilder::new().all(matches!(prune_mode, BuildPruneMode::All)).build();
+
+    let prune_build_response = match docker.prune_build(Some(prune_build_options)).await {
+        Ok(response) => response,
+        Err(e) => {
+            let message = format!("Failed to prune build cache. (Internal error: `{}`).", e);
+            error!("\t-> {}", message);
+            report.errors.push(message);
+            return report;
+        }
+    };
+
+    let caches_deleted = prune_build_response.caches_deleted.unwrap_or_default().len();
+    let bytes_reclaimed = prune_build_response.space_reclaimed.unwrap_or_default().max(0) as u64;
+
+    info!(
+        "\t-> Prune completed successfully.\n\t\t- Removed `{}` build cache entries.\n\t\t- Reclaimed `{}` bytes.",
+        caches_deleted, bytes_reclaimed
+    );
+
+    report.items_removed = caches_deleted;
+    report.bytes_reclaimed = bytes_reclaimed;
+    report
 }
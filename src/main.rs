@@ -1,26 +1,62 @@
 mod maid;
 mod utils;
 mod enums;
+mod duty;
+mod endpoints;
+mod metrics;
+mod state;
 
-use crate::maid::housekeeping;
-use crate::utils::connect_to_docker;
+use crate::endpoints::connect_to_endpoints;
+use crate::maid::{housekeeping, spawn_duty_managers};
+use crate::state::StateStore;
 
 use std::env;
 use std::str::FromStr;
+use std::time::Duration;
 use chrono::Local;
 use cron::Schedule;
-use log::{info, warn};
+use log::{error, info, warn};
+use tokio::signal::unix::{signal, SignalKind};
 use crate::enums::ImagesPruneMode;
 
+/// Default grace period granted to an in-flight housekeeping round to finish after a shutdown
+/// signal is received, before the maid force-exits.
+const DEFAULT_SHUTDOWN_GRACE_SECS: u64 = 60;
+
+/// Waits for either SIGTERM or SIGINT, whichever arrives first.
+async fn shutdown_signal() {
+    let mut sigterm = signal(SignalKind::terminate()).expect("Failed to register SIGTERM handler");
+
+    tokio::select! {
+        _ = sigterm.recv() => info!("Received SIGTERM."),
+        _ = tokio::signal::ctrl_c() => info!("Received SIGINT."),
+    }
+}
+
+/// Reads `MAID_SHUTDOWN_GRACE` (in seconds) from the environment, falling back to
+/// `DEFAULT_SHUTDOWN_GRACE_SECS` if unset or invalid.
+fn shutdown_grace() -> Duration {
+    let secs = env::var("MAID_SHUTDOWN_GRACE")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_SHUTDOWN_GRACE_SECS);
+
+    Duration::from_secs(secs)
+}
+
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
     colog::init();
     info!("Doing some checks before planning housekeeping duties...");
 
-    // Ensure access to docker socket with bollard
-    {
-        let _ = connect_to_docker();
-    }
+    // Connect to every configured Docker endpoint up-front, so a bad endpoint is caught before scheduling
+    let endpoints = connect_to_endpoints().expect("Failed to connect to any configured Docker endpoint");
+
+    // Load the persisted digest state, so a restart doesn't forget what was already updated
+    let state = StateStore::load_shared();
+
+    // Register one duty manager per endpoint, kept alive for the life of the process
+    let duty_managers = spawn_duty_managers(&endpoints, state.clone()).await;
 
     // Print a summary of the applied configuration
     {
@@ -34,6 +70,11 @@ async fn main() {
         info!("{}", summary);
     }
 
+    // Start the metrics endpoint if one was requested, also exposing duty status and control
+    if let Ok(metrics_addr) = env::var("MAID_METRICS_ADDR") {
+        tokio::spawn(metrics::serve(metrics_addr, duty_managers.clone()));
+    }
+
     // Schedule initialization
     let schedule_string = env::var("MAID_SCHEDULE").unwrap_or_else(|_| {
         warn!("MAID_SCHEDULE not set, falling back to default schedule (every 6 hour): 0 0 */6 * * *");
@@ -44,19 +85,55 @@ async fn main() {
     // Run housekeeping immediately if requested
     if env::var("MAID_RUN_ON_STARTUP").map(|v| v == "true").unwrap_or(false) {
         info!("MAID_RUN_ON_STARTUP is set to `true`, running housekeeping duties immediately.");
-        housekeeping().await;
+        housekeeping(&duty_managers, &state).await;
     }
 
     // Schedule housekeeping duties
     info!("House is quiet. Maid standing by.");
-    loop {
+    'scheduler: loop {
         if let Some(next) = schedule.upcoming(Local).next() {
             info!("Next housekeeping round scheduled at {}", next.format("%H:%M:%S %d-%m-%Y"));
 
             if let Ok(duration) = next.signed_duration_since(Local::now()).to_std() {
-                tokio::time::sleep(duration).await;
-                housekeeping().await;
+                tokio::select! {
+                    _ = tokio::time::sleep(duration) => {
+                        // Run the round on its own task so a shutdown signal arriving mid-housekeeping
+                        // can be raced against it instead of being blocked behind the `.await`.
+                        let duty_managers = duty_managers.clone();
+                        let state = state.clone();
+                        let round = tokio::spawn(async move { housekeeping(&duty_managers, &state).await });
+
+                        tokio::pin!(round);
+                        tokio::select! {
+                            result = &mut round => {
+                                if let Err(e) = result {
+                                    error!("Housekeeping round panicked: `{}`.", e);
+                                }
+                            }
+                            _ = shutdown_signal() => {
+                                info!(
+                                    "Shutdown requested while housekeeping is in progress, letting it reach a safe checkpoint (grace: `{:?}`)...",
+                                    shutdown_grace()
+                                );
+
+                                match tokio::time::timeout(shutdown_grace(), round).await {
+                                    Ok(Ok(())) => info!("Housekeeping round finished, shutting down."),
+                                    Ok(Err(e)) => error!("Housekeeping round panicked: `{}`.", e),
+                                    Err(_) => warn!("Grace period elapsed before housekeeping finished, forcing exit."),
+                                }
+
+                                break 'scheduler;
+                            }
+                        }
+                    }
+                    _ = shutdown_signal() => {
+                        info!("Shutdown requested, no housekeeping round in progress. Exiting.");
+                        break 'scheduler;
+                    }
+                }
             }
         }
     }
+
+    info!("Maid has left the building.");
 }
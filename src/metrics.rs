@@ -0,0 +1,257 @@
+use crate::duty::DutyManagers;
+use log::{error, info};
+use std::collections::HashMap;
+use std::env;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Header carrying the shared secret required to pause or resume a duty, checked against
+/// `MAID_METRICS_TOKEN`. The metrics/status routes stay open since they're read-only.
+const CONTROL_TOKEN_HEADER: &str = "x-maid-token";
+
+/// Process-wide counters and gauges describing housekeeping outcomes.
+#[derive(Default)]
+pub struct Metrics {
+    pub images_pruned_total: AtomicU64,
+    pub build_cache_items_pruned_total: AtomicU64,
+    pub bytes_reclaimed_total: AtomicU64,
+    pub containers_checked_total: AtomicU64,
+    pub containers_updated_total: AtomicU64,
+    pub containers_skipped_total: AtomicU64,
+    pub update_failures_total: AtomicU64,
+    pub rollbacks_total: AtomicU64,
+    pub last_round_timestamp_seconds: AtomicU64,
+    pub last_round_duration_seconds: AtomicU64,
+}
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// Returns the process-wide metrics instance, creating it on first use.
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(Metrics::default)
+}
+
+impl Metrics {
+    /// Records the timestamp and duration of a just-finished housekeeping round.
+    pub fn record_round(&self, started_at: SystemTime, duration_secs: u64) {
+        let timestamp = started_at.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+        self.last_round_timestamp_seconds.store(timestamp, Ordering::Relaxed);
+        self.last_round_duration_seconds.store(duration_secs, Ordering::Relaxed);
+    }
+
+    /// Renders every metric in the Prometheus text exposition format.
+    fn render(&self) -> String {
+        let mut out = String::new();
+
+        push_metric(
+            &mut out,
+            "counter",
+            "docker_maid_images_pruned_total",
+            "Number of images removed by the prune_images duty.",
+            self.images_pruned_total.load(Ordering::Relaxed),
+        );
+        push_metric(
+            &mut out,
+            "counter",
+            "docker_maid_build_cache_items_pruned_total",
+            "Number of build cache entries removed by the prune_build_cache duty.",
+            self.build_cache_items_pruned_total.load(Ordering::Relaxed),
+        );
+        push_metric(
+            &mut out,
+            "counter",
+            "docker_maid_bytes_reclaimed_total",
+            "Bytes reclaimed across all prune duties.",
+            self.bytes_reclaimed_total.load(Ordering::Relaxed),
+        );
+        push_metric(
+            &mut out,
+            "counter",
+            "docker_maid_containers_checked_total",
+            "Number of containers inspected by the update_images duty.",
+            self.containers_checked_total.load(Ordering::Relaxed),
+        );
+        push_metric(
+            &mut out,
+            "counter",
+            "docker_maid_containers_updated_total",
+            "Number of containers successfully recreated on a new image digest.",
+            self.containers_updated_total.load(Ordering::Relaxed),
+        );
+        push_metric(
+            &mut out,
+            "counter",
+            "docker_maid_containers_skipped_total",
+            "Number of containers skipped during an update round.",
+            self.containers_skipped_total.load(Ordering::Relaxed),
+        );
+        push_metric(
+            &mut out,
+            "counter",
+            "docker_maid_update_failures_total",
+            "Number of container updates that failed.",
+            self.update_failures_total.load(Ordering::Relaxed),
+        );
+        push_metric(
+            &mut out,
+            "counter",
+            "docker_maid_rollbacks_total",
+            "Number of container updates rolled back after failing their health check.",
+            self.rollbacks_total.load(Ordering::Relaxed),
+        );
+        push_metric(
+            &mut out,
+            "gauge",
+            "docker_maid_last_round_timestamp_seconds",
+            "Unix timestamp of the last completed housekeeping round.",
+            self.last_round_timestamp_seconds.load(Ordering::Relaxed),
+        );
+        push_metric(
+            &mut out,
+            "gauge",
+            "docker_maid_last_round_duration_seconds",
+            "Duration of the last completed housekeeping round, in seconds.",
+            self.last_round_duration_seconds.load(Ordering::Relaxed),
+        );
+
+        out
+    }
+}
+
+/// Appends one metric's `HELP`/`TYPE`/value lines to `out`, where `kind` is `"counter"` or `"gauge"`.
+fn push_metric(out: &mut String, kind: &str, name: &str, help: &str, value: u64) {
+    out.push_str(&format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"));
+}
+
+/// Serves the process metrics, plus duty status and control under `/duties`, over plain HTTP at
+/// `addr`. `/duties/pause` and `/duties/resume` mutate live housekeeping state, so they require
+/// an `X-Maid-Token` header matching `MAID_METRICS_TOKEN`; if that variable isn't set, both are
+/// refused outright rather than left reachable by anyone who can hit this address. Intended to
+/// be spawned as a background task; it runs until the process exits.
+pub async fn serve(addr: String, duty_managers: DutyManagers) {
+    let listener = match TcpListener::bind(&addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Failed to bind metrics endpoint on `{}`. (Internal error: `{}`).", addr, e);
+            return;
+        }
+    };
+
+    info!("Metrics endpoint listening on `{}`.", addr);
+
+    loop {
+        let (stream, _) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                error!("Failed to accept metrics connection. (Internal error: `{}`).", e);
+                continue;
+            }
+        };
+
+        tokio::spawn(handle_connection(stream, duty_managers.clone()));
+    }
+}
+
+/// Handles a single connection: `/duties` reports every endpoint's duty statuses, `/duties/pause`
+/// and `/duties/resume` (both taking `?endpoint=...&duty=...`) control them, and everything else
+/// renders the Prometheus metrics.
+async fn handle_connection(stream: TcpStream, duty_managers: DutyManagers) {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+
+    if reader.read_line(&mut request_line).await.is_err() {
+        return;
+    }
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut header_line = String::new();
+        match reader.read_line(&mut header_line).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) if header_line.trim().is_empty() => break,
+            Ok(_) => {
+                if let Some((name, value)) = header_line.split_once(':') {
+                    headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+                }
+            }
+        }
+    }
+
+    let target = request_line.split_whitespace().nth(1).unwrap_or("/").to_string();
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path, query.to_string()),
+        None => (target.as_str(), String::new()),
+    };
+
+    let body = match path {
+        "/duties" => render_duty_statuses(&duty_managers).await,
+        "/duties/pause" => control_duty(&duty_managers, &query, &headers, true).await,
+        "/duties/resume" => control_duty(&duty_managers, &query, &headers, false).await,
+        _ => metrics().render(),
+    };
+
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+
+    let mut stream = reader.into_inner();
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        error!("Failed to write metrics response. (Internal error: `{}`).", e);
+    }
+}
+
+/// Renders every endpoint's duty statuses as plain text, one line per duty.
+async fn render_duty_statuses(duty_managers: &DutyManagers) -> String {
+    let mut out = String::new();
+
+    for (endpoint_name, handle) in duty_managers.iter() {
+        for status in handle.status().await {
+            out.push_str(&format!(
+                "endpoint={} duty={} state={:?} paused={} last_report={:?}\n",
+                endpoint_name, status.name, status.state, status.paused, status.last_report
+            ));
+        }
+    }
+
+    out
+}
+
+/// Pauses or resumes the duty named by the `endpoint` and `duty` query parameters, after
+/// checking `headers` carries a token matching `MAID_METRICS_TOKEN`.
+async fn control_duty(duty_managers: &DutyManagers, query: &str, headers: &HashMap<String, String>, pause: bool) -> String {
+    if !is_authorized(headers) {
+        return "forbidden: missing or invalid X-Maid-Token header\n".to_string();
+    }
+
+    let params: HashMap<&str, &str> = query.split('&').filter_map(|pair| pair.split_once('=')).collect();
+
+    let (Some(endpoint), Some(duty)) = (params.get("endpoint"), params.get("duty")) else {
+        return "missing `endpoint` or `duty` query parameter\n".to_string();
+    };
+
+    let Some(handle) = duty_managers.get(*endpoint) else {
+        return format!("unknown endpoint `{}`\n", endpoint);
+    };
+
+    if pause {
+        handle.pause(*duty).await;
+    } else {
+        handle.resume(*duty).await;
+    }
+
+    "OK\n".to_string()
+}
+
+/// Checks `headers` for an `X-Maid-Token` value matching `MAID_METRICS_TOKEN`. Refuses by
+/// default: with no token configured, the control endpoints stay disabled.
+fn is_authorized(headers: &HashMap<String, String>) -> bool {
+    match env::var("MAID_METRICS_TOKEN") {
+        Ok(token) if !token.is_empty() => headers.get(CONTROL_TOKEN_HEADER) == Some(&token),
+        _ => false,
+    }
+}
@@ -0,0 +1,106 @@
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::RwLock;
+
+/// Shared handle to the persisted digest store, so it can be read and written from any round.
+pub type StateHandle = Arc<RwLock<StateStore>>;
+
+/// What we know about the last time a container's image digest was checked or updated.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DigestEntry {
+    pub digest: String,
+    pub last_check_seconds: u64,
+    pub last_update_seconds: Option<u64>,
+    pub failure_count: u32,
+}
+
+/// Persists known container/image digests under `MAID_STATE_DIR` across restarts.
+#[derive(Debug, Default)]
+pub struct StateStore {
+    path: PathBuf,
+    entries: HashMap<String, DigestEntry>,
+}
+
+impl StateStore {
+    /// Loads the digest store from `MAID_STATE_DIR/digests.json`, or starts empty if it doesn't
+    /// exist yet or fails to parse.
+    pub fn load() -> Self {
+        let state_dir = env::var("MAID_STATE_DIR").unwrap_or_else(|_| "/var/lib/docker-maid".to_string());
+        let path = Path::new(&state_dir).join("digests.json");
+
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| match serde_json::from_str(&contents) {
+                Ok(entries) => Some(entries),
+                Err(e) => {
+                    error!("Failed to parse state file `{}`, starting empty. (Internal error: `{}`).", path.display(), e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self { path, entries }
+    }
+
+    /// Wraps a freshly loaded store in a shareable, lockable handle.
+    pub fn load_shared() -> StateHandle {
+        Arc::new(RwLock::new(Self::load()))
+    }
+
+    /// Returns the entry recorded for `key`, if any.
+    pub fn entry(&self, key: &str) -> Option<&DigestEntry> {
+        self.entries.get(key)
+    }
+
+    /// Records that `key` was checked and found at `digest`, marking it as updated when `updated`
+    /// is set and clearing any prior failure count, since this check succeeded.
+    pub fn record_check(&mut self, key: &str, digest: &str, updated: bool) {
+        let entry = self.entries.entry(key.to_string()).or_default();
+        entry.digest = digest.to_string();
+        entry.last_check_seconds = now_seconds();
+        entry.failure_count = 0;
+        if updated {
+            entry.last_update_seconds = Some(now_seconds());
+        }
+    }
+
+    /// Records a failed update attempt for `key` against the digest that was attempted, so the
+    /// same broken digest isn't recreated again every round, and bumps its failure count.
+    pub fn record_failure(&mut self, key: &str, digest: &str) {
+        let entry = self.entries.entry(key.to_string()).or_default();
+        entry.digest = digest.to_string();
+        entry.last_check_seconds = now_seconds();
+        entry.failure_count += 1;
+    }
+
+    /// Writes the store to `MAID_STATE_DIR/digests.json`, creating the directory if needed.
+    pub fn save(&self) {
+        if let Some(parent) = self.path.parent() {
+            if let Err(e) = std::fs::create_dir_all(parent) {
+                error!("Failed to create state directory `{}`. (Internal error: `{}`).", parent.display(), e);
+                return;
+            }
+        }
+
+        let json = match serde_json::to_string_pretty(&self.entries) {
+            Ok(json) => json,
+            Err(e) => {
+                error!("Failed to serialize state. (Internal error: `{}`).", e);
+                return;
+            }
+        };
+
+        if let Err(e) = std::fs::write(&self.path, json) {
+            error!("Failed to persist state to `{}`. (Internal error: `{}`).", self.path.display(), e);
+        }
+    }
+}
+
+pub(crate) fn now_seconds() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}